@@ -0,0 +1,54 @@
+// Bitcoin Dev Kit
+// Written in 2020 by Alekos Filini <alekos.filini@gmail.com>
+//
+// Copyright (c) 2020-2021 Bitcoin Dev Kit Developers
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Electrum blockchain backend.
+
+use alloc::string::ToString;
+
+use electrum_client::ElectrumApi;
+
+use crate::blockchain::FeeEstimator;
+use crate::error::Error;
+use crate::types::FeeRate;
+
+/// A [`Blockchain`](crate::blockchain) backend that talks to an Electrum server.
+pub struct ElectrumBlockchain {
+    client: electrum_client::Client,
+}
+
+impl ElectrumBlockchain {
+    /// Create a new [`ElectrumBlockchain`] wrapping an existing client.
+    pub fn new(client: electrum_client::Client) -> Self {
+        ElectrumBlockchain { client }
+    }
+}
+
+impl FeeEstimator for ElectrumBlockchain {
+    /// Ask the server for a fee estimate via `blockchain.estimatefee`.
+    ///
+    /// The server replies with a recommended fee rate in BTC/kvB for the
+    /// requested confirmation target, or `-1` if it doesn't have enough data
+    /// to produce one. In that case we fall back to
+    /// [`FeeRate::default_min_relay_fee`] instead of letting a negative value
+    /// reach [`FeeRate::new_checked`](crate::types::FeeRate), which would panic.
+    fn estimate(&self, target_blocks: u16) -> Result<FeeRate, Error> {
+        let btc_per_kvb = self
+            .client
+            .estimate_fee(target_blocks as usize)
+            .map_err(|e| Error::Generic(e.to_string()))?;
+
+        if btc_per_kvb <= 0.0 {
+            return Ok(FeeRate::default_min_relay_fee());
+        }
+
+        Ok(FeeRate::from_btc_per_kvb(btc_per_kvb as f32))
+    }
+}
@@ -0,0 +1,50 @@
+// Bitcoin Dev Kit
+// Written in 2020 by Alekos Filini <alekos.filini@gmail.com>
+//
+// Copyright (c) 2020-2021 Bitcoin Dev Kit Developers
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Blockchain backends and fee estimation.
+//!
+//! A blockchain backend that wants to support fee estimation implements
+//! [`FeeEstimator`], which lets callers ask for a [`FeeRate`] in terms of a
+//! desired confirmation target rather than hardcoding a sat/vb constant.
+
+#[cfg(feature = "electrum")]
+pub mod electrum;
+
+use crate::error::Error;
+use crate::types::FeeRate;
+
+/// A target to aim for when selecting the fee rate of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeTarget {
+    /// Confirm within the given number of blocks.
+    Blocks(u16),
+    /// Use this exact [`FeeRate`], bypassing estimation.
+    Explicit(FeeRate),
+    /// Use [`FeeRate::default_min_relay_fee`]; the backend is not queried.
+    MinRelay,
+}
+
+/// Trait implemented by blockchain backends that can provide fee rate estimates.
+pub trait FeeEstimator {
+    /// Estimate a [`FeeRate`] that should get a transaction confirmed within
+    /// `target_blocks` blocks.
+    fn estimate(&self, target_blocks: u16) -> Result<FeeRate, Error>;
+
+    /// Resolve a [`FeeTarget`] into a concrete [`FeeRate`], estimating one from
+    /// the backend when the target is given in blocks.
+    fn fee_rate(&self, target: FeeTarget) -> Result<FeeRate, Error> {
+        match target {
+            FeeTarget::Blocks(target_blocks) => self.estimate(target_blocks),
+            FeeTarget::Explicit(fee_rate) => Ok(fee_rate),
+            FeeTarget::MinRelay => Ok(FeeRate::default_min_relay_fee()),
+        }
+    }
+}
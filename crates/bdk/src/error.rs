@@ -0,0 +1,73 @@
+// Bitcoin Dev Kit
+// Written in 2020 by Alekos Filini <alekos.filini@gmail.com>
+//
+// Copyright (c) 2020-2021 Bitcoin Dev Kit Developers
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Errors shared by the wallet and blockchain backends.
+
+use alloc::string::String;
+use core::fmt;
+
+use bitcoin::hash_types::Txid;
+
+/// Errors that can be thrown by this library
+#[derive(Debug)]
+pub enum Error {
+    /// Generic error, usually forwarded from a dependency
+    Generic(String),
+    /// The transaction being bumped does not signal for RBF (no input has a sequence number
+    /// below `0xFFFFFFFE`)
+    IrreplaceableTransaction,
+    /// The transaction being bumped is already confirmed and can no longer be replaced
+    TransactionConfirmed(Txid),
+    /// Not enough funds in the wallet's available UTXOs to reach the requested fee rate
+    InsufficientFunds {
+        /// Amount needed, in satoshis
+        needed: u64,
+        /// Amount available, in satoshis
+        available: u64,
+    },
+    /// The requested fee does not satisfy BIP-125 rule 4: it must exceed the original
+    /// fee by at least the feerate increase over the original applied to the replacement's size
+    FeeTooLow {
+        /// The minimum absolute fee, in satoshis, that would be accepted
+        required: u64,
+    },
+    /// The given output index is not a valid vout on the transaction
+    InvalidVout {
+        /// The out-of-range index that was supplied
+        vout: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Generic(err) => write!(f, "{}", err),
+            Error::IrreplaceableTransaction => {
+                write!(f, "transaction does not signal for RBF")
+            }
+            Error::TransactionConfirmed(txid) => {
+                write!(f, "transaction {} is already confirmed", txid)
+            }
+            Error::InsufficientFunds { needed, available } => write!(
+                f,
+                "insufficient funds: {} sat needed, {} sat available",
+                needed, available
+            ),
+            Error::FeeTooLow { required } => {
+                write!(f, "fee too low: at least {} sat required", required)
+            }
+            Error::InvalidVout { vout } => write!(f, "output index {} is out of range", vout),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
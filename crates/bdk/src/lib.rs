@@ -0,0 +1,24 @@
+// Bitcoin Dev Kit
+// Written in 2020 by Alekos Filini <alekos.filini@gmail.com>
+//
+// Copyright (c) 2020-2021 Bitcoin Dev Kit Developers
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! A modern, lightweight, descriptor-based wallet library written in Rust.
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod blockchain;
+pub mod error;
+pub mod types;
+pub mod wallet;
+
+pub use error::Error;
+pub use types::{FeeRate, KeychainKind, LocalUtxo, TransactionDetails, Utxo, Vbytes, WeightedUtxo};
@@ -128,6 +128,18 @@ impl FeeRate {
     pub fn fee_vb(&self, vbytes: usize) -> u64 {
         (self.as_sat_per_vb() * vbytes as f32).ceil() as u64
     }
+
+    /// Return `self` clamped to `max`, whichever is lower.
+    ///
+    /// Useful to cap a desired fee rate to a safety ceiling before it's used
+    /// to bump or replace a transaction.
+    pub fn clamp(self, max: FeeRate) -> FeeRate {
+        if self > max {
+            max
+        } else {
+            self
+        }
+    }
 }
 
 impl Default for FeeRate {
@@ -144,6 +156,41 @@ impl Sub for FeeRate {
     }
 }
 
+/// A policy that bounds how high a [`FeeRate`] is allowed to go when bumping
+/// or replacing a transaction.
+///
+/// Fee spikes can otherwise push an RBF or CPFP bump to an absurd value, so the
+/// desired rate is capped to a ceiling: the higher of an absolute maximum and a
+/// multiple of the previous transaction's fee rate, so `absolute_max` still acts
+/// as a floor for the ceiling when the previous fee rate was very low.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeBumpPolicy {
+    /// An absolute [`FeeRate`] ceiling; the effective ceiling is never lower than this.
+    pub absolute_max: FeeRate,
+    /// A multiple of the previous transaction's fee rate used as a ceiling; the effective
+    /// ceiling is the higher of this and `absolute_max`.
+    pub relative_max_multiplier: f32,
+}
+
+impl FeeBumpPolicy {
+    /// Apply this policy to a `desired` fee rate given the `previous` fee rate
+    /// being replaced or bumped.
+    ///
+    /// Returns `min(desired, max(absolute_max, previous * relative_max_multiplier))`.
+    pub fn apply(&self, desired: FeeRate, previous: FeeRate) -> FeeRate {
+        let relative_max = FeeRate::new_checked(
+            (previous.as_sat_per_vb() * self.relative_max_multiplier).max(0.0),
+        );
+        let ceiling = if relative_max > self.absolute_max {
+            relative_max
+        } else {
+            self.absolute_max
+        };
+
+        desired.clamp(ceiling)
+    }
+}
+
 /// Trait implemented by types that can be used to measure weight units.
 pub trait Vbytes {
     /// Convert weight units to virtual bytes.
@@ -157,6 +204,13 @@ impl Vbytes for usize {
     }
 }
 
+/// The non-witness overhead, in vbytes, of a single transaction input: the 36-byte
+/// outpoint, the 4-byte sequence number, and a 1-byte empty `scriptSig` length.
+///
+/// Used together with a [`WeightedUtxo::satisfaction_weight`] (which only covers the
+/// witness/`scriptSig` contents) to size a transaction input's full vsize contribution.
+pub(crate) const TXIN_BASE_VBYTES: usize = 41;
+
 /// An unspent output owned by a [`Wallet`].
 ///
 /// [`Wallet`]: crate::Wallet
@@ -188,6 +242,41 @@ pub struct WeightedUtxo {
     pub utxo: Utxo,
 }
 
+/// Standard script types with a well-known `satisfaction_weight`, used to build a
+/// [`WeightedUtxo`] without hardcoding weights pulled from a sample testnet transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StandardScript {
+    /// Pay-to-witness-pubkey-hash (native segwit v0)
+    P2wpkh,
+    /// Pay-to-taproot, key-path spend
+    P2trKeySpend,
+    /// Pay-to-script-hash wrapping a P2WPKH redeem script (nested segwit)
+    P2shP2wpkh,
+}
+
+impl StandardScript {
+    /// The approximate weight, in weight units, of a satisfying witness/`scriptSig` for
+    /// this script type.
+    fn satisfaction_weight(self) -> usize {
+        match self {
+            StandardScript::P2wpkh => 108,
+            StandardScript::P2trKeySpend => 66,
+            StandardScript::P2shP2wpkh => 200,
+        }
+    }
+}
+
+impl WeightedUtxo {
+    /// Create a [`WeightedUtxo`] deriving `satisfaction_weight` from a [`StandardScript`]
+    /// type, for integrating foreign inputs whose witness size is unknown upfront.
+    pub fn new_standard(utxo: Utxo, script_type: StandardScript) -> Self {
+        WeightedUtxo {
+            satisfaction_weight: script_type.satisfaction_weight(),
+            utxo,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// An unspent transaction output (UTXO).
 pub enum Utxo {
@@ -249,11 +338,22 @@ pub struct TransactionDetails {
     pub sent: u64,
     /// Fee value in sats if it was available.
     pub fee: Option<u64>,
+    /// The transaction's virtual size in vbytes, if it was available at build/sign time.
+    pub vsize: Option<usize>,
     /// If the transaction is confirmed, contains height and Unix timestamp of the block containing the
     /// transaction, unconfirmed transaction contains `None`.
     pub confirmation_time: ConfirmationTime,
 }
 
+impl TransactionDetails {
+    /// The effective [`FeeRate`] paid by this transaction, computed from `fee` and `vsize`.
+    ///
+    /// Returns `None` if either is unavailable.
+    pub fn fee_rate(&self) -> Option<FeeRate> {
+        Some(FeeRate::from_vb(self.fee?, self.vsize?))
+    }
+}
+
 impl PartialOrd for TransactionDetails {
     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         Some(self.cmp(other))
@@ -336,4 +436,118 @@ mod tests {
         assert!((fee.as_sat_per_vb() - 1.0).abs() < f32::EPSILON);
         assert_eq!(fee.sat_per_kwu(), 250.0);
     }
+
+    #[test]
+    fn test_feerate_clamp() {
+        let low = FeeRate::from_sat_per_vb(5.0);
+        let high = FeeRate::from_sat_per_vb(50.0);
+        let max = FeeRate::from_sat_per_vb(10.0);
+
+        assert_eq!(low.clamp(max), low);
+        assert_eq!(high.clamp(max), max);
+    }
+
+    #[test]
+    fn test_fee_bump_policy_uses_absolute_max() {
+        let policy = FeeBumpPolicy {
+            absolute_max: FeeRate::from_sat_per_vb(100.0),
+            relative_max_multiplier: 2.0,
+        };
+        let previous = FeeRate::from_sat_per_vb(10.0);
+        let desired = FeeRate::from_sat_per_vb(1000.0);
+
+        assert_eq!(policy.apply(desired, previous), policy.absolute_max);
+    }
+
+    #[test]
+    fn test_fee_bump_policy_uses_relative_max() {
+        let policy = FeeBumpPolicy {
+            absolute_max: FeeRate::from_sat_per_vb(5.0),
+            relative_max_multiplier: 3.0,
+        };
+        let previous = FeeRate::from_sat_per_vb(10.0);
+        let desired = FeeRate::from_sat_per_vb(1000.0);
+
+        assert_eq!(policy.apply(desired, previous), FeeRate::from_sat_per_vb(30.0));
+    }
+
+    #[test]
+    fn test_fee_bump_policy_never_exceeds_desired() {
+        let policy = FeeBumpPolicy {
+            absolute_max: FeeRate::from_sat_per_vb(100.0),
+            relative_max_multiplier: 10.0,
+        };
+        let previous = FeeRate::from_sat_per_vb(10.0);
+        let desired = FeeRate::from_sat_per_vb(15.0);
+
+        assert_eq!(policy.apply(desired, previous), desired);
+    }
+
+    #[test]
+    fn test_transaction_details_fee_rate() {
+        use core::str::FromStr;
+
+        let txid = Txid::from_str(
+            "e6d48fab8f7e1e1f7e5a9e2b2f2c2b2c2f2e2d2c2b2a29282726252423222120",
+        )
+        .unwrap();
+        let details = TransactionDetails {
+            transaction: None,
+            txid,
+            received: 0,
+            sent: 1_000,
+            fee: Some(200),
+            vsize: Some(200),
+            confirmation_time: ConfirmationTime::Unconfirmed { last_seen: 0 },
+        };
+
+        assert!((details.fee_rate().unwrap().as_sat_per_vb() - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_transaction_details_fee_rate_missing_vsize() {
+        use core::str::FromStr;
+
+        let txid = Txid::from_str(
+            "e6d48fab8f7e1e1f7e5a9e2b2f2c2b2c2f2e2d2c2b2a29282726252423222120",
+        )
+        .unwrap();
+        let details = TransactionDetails {
+            transaction: None,
+            txid,
+            received: 0,
+            sent: 1_000,
+            fee: Some(200),
+            vsize: None,
+            confirmation_time: ConfirmationTime::Unconfirmed { last_seen: 0 },
+        };
+
+        assert_eq!(details.fee_rate(), None);
+    }
+
+    #[test]
+    fn test_weighted_utxo_new_standard() {
+        use core::str::FromStr;
+
+        let utxo = Utxo::Local(LocalUtxo {
+            outpoint: OutPoint::new(
+                Txid::from_str(
+                    "e6d48fab8f7e1e1f7e5a9e2b2f2c2b2c2f2e2d2c2b2a29282726252423222120",
+                )
+                .unwrap(),
+                0,
+            ),
+            txout: TxOut {
+                value: 10_000,
+                script_pubkey: bitcoin::blockdata::script::Script::new(),
+            },
+            keychain: KeychainKind::External,
+            is_spent: false,
+            derivation_index: 0,
+            confirmation_time: ConfirmationTime::Unconfirmed { last_seen: 0 },
+        });
+
+        let weighted = WeightedUtxo::new_standard(utxo, StandardScript::P2wpkh);
+        assert_eq!(weighted.satisfaction_weight, 108);
+    }
 }
@@ -0,0 +1,415 @@
+// Bitcoin Dev Kit
+// Written in 2020 by Alekos Filini <alekos.filini@gmail.com>
+//
+// Copyright (c) 2020-2021 Bitcoin Dev Kit Developers
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! RBF fee-bumping, analogous to Bitcoin Core's `bumpfee` RPC.
+
+use alloc::vec::Vec;
+
+use bdk_chain::ConfirmationTime;
+use bitcoin::blockdata::transaction::Transaction;
+
+use crate::blockchain::FeeEstimator;
+use crate::error::Error;
+use crate::types::{FeeRate, TransactionDetails, Vbytes, WeightedUtxo, TXIN_BASE_VBYTES};
+
+/// Sequence number below which an input signals replaceability, per BIP-125.
+const RBF_SEQUENCE_THRESHOLD: u32 = 0xFFFF_FFFE;
+
+/// Builds a replacement for an unconfirmed, signal-for-RBF transaction that pays a higher fee.
+///
+/// Mirrors the options of Core's `bumpfee` RPC: an explicit target [`FeeRate`], or a
+/// confirmation target resolved through a [`FeeEstimator`], and whether the existing
+/// change output may be reduced to cover the new fee.
+pub struct FeeBumpBuilder<'a> {
+    original_tx: &'a Transaction,
+    original_details: &'a TransactionDetails,
+    change_vout: Option<usize>,
+    extra_utxos: Vec<WeightedUtxo>,
+    fee_rate: Option<FeeRate>,
+    conf_target: Option<u16>,
+    allow_shrinking: bool,
+}
+
+impl<'a> FeeBumpBuilder<'a> {
+    /// Start building a replacement for `original_tx`.
+    pub fn new(original_tx: &'a Transaction, original_details: &'a TransactionDetails) -> Self {
+        FeeBumpBuilder {
+            original_tx,
+            original_details,
+            change_vout: None,
+            extra_utxos: Vec::new(),
+            fee_rate: None,
+            conf_target: None,
+            allow_shrinking: false,
+        }
+    }
+
+    /// Pay exactly this [`FeeRate`], bypassing estimation.
+    pub fn fee_rate(mut self, fee_rate: FeeRate) -> Self {
+        self.fee_rate = Some(fee_rate);
+        self
+    }
+
+    /// Resolve the fee rate from a confirmation target, via the [`FeeEstimator`] passed to
+    /// [`finish`](Self::finish).
+    pub fn fee_target(mut self, target_blocks: u16) -> Self {
+        self.conf_target = Some(target_blocks);
+        self
+    }
+
+    /// Allow the output at `vout` to be reduced in value to pay the higher fee.
+    pub fn allow_shrinking(mut self, vout: usize) -> Self {
+        self.change_vout = Some(vout);
+        self.allow_shrinking = true;
+        self
+    }
+
+    /// Additional UTXOs the wallet can draw on if the existing inputs and change output
+    /// cannot cover the higher fee on their own.
+    ///
+    /// Every satoshi contributed by these UTXOs beyond what's needed to hit the target fee
+    /// is paid to the miner rather than returned as change, whether or not an output is
+    /// marked with [`allow_shrinking`](Self::allow_shrinking): that output is only ever
+    /// reduced, never increased.
+    pub fn add_utxos(mut self, utxos: Vec<WeightedUtxo>) -> Self {
+        self.extra_utxos.extend(utxos);
+        self
+    }
+
+    /// Finalize the replacement transaction.
+    ///
+    /// The incremental feerate required by BIP-125 rule 4 is computed via [`Sub for
+    /// FeeRate`](FeeRate#impl-Sub-for-FeeRate) as `target_fee_rate - old_fee_rate`, clamped
+    /// to zero so a caller-supplied `target_fee_rate` below the original's never produces a
+    /// negative requirement.
+    pub fn finish(
+        self,
+        fee_estimator: &impl FeeEstimator,
+    ) -> Result<(Transaction, TransactionDetails), Error> {
+        if !self
+            .original_tx
+            .input
+            .iter()
+            .any(|txin| txin.sequence < RBF_SEQUENCE_THRESHOLD)
+        {
+            return Err(Error::IrreplaceableTransaction);
+        }
+        if !matches!(
+            self.original_details.confirmation_time,
+            ConfirmationTime::Unconfirmed { .. }
+        ) {
+            return Err(Error::TransactionConfirmed(self.original_details.txid));
+        }
+        if let Some(vout) = self.change_vout {
+            if vout >= self.original_tx.output.len() {
+                return Err(Error::InvalidVout { vout });
+            }
+        }
+
+        let old_fee = self.original_details.fee.unwrap_or(0);
+        let old_vsize = self.original_tx.weight().vbytes();
+
+        let mut new_tx = self.original_tx.clone();
+        let added_input_value: u64 = self
+            .extra_utxos
+            .iter()
+            .map(|utxo| utxo.utxo.txout().value)
+            .sum();
+        for utxo in &self.extra_utxos {
+            new_tx.input.push(bitcoin::blockdata::transaction::TxIn {
+                previous_output: utxo.utxo.outpoint(),
+                script_sig: bitcoin::blockdata::script::Script::new(),
+                sequence: RBF_SEQUENCE_THRESHOLD,
+                witness: bitcoin::blockdata::witness::Witness::new(),
+            });
+        }
+        let added_weight: usize = self.extra_utxos.iter().map(|u| u.satisfaction_weight).sum();
+        let new_vsize =
+            old_vsize + self.extra_utxos.len() * TXIN_BASE_VBYTES + added_weight.vbytes();
+
+        let target_fee_rate = match (self.fee_rate, self.conf_target) {
+            (Some(fee_rate), _) => fee_rate,
+            (None, Some(target_blocks)) => fee_estimator.estimate(target_blocks)?,
+            (None, None) => fee_estimator.estimate(1)?,
+        };
+
+        // BIP-125 rule 4: the replacement must pay an absolute fee that is at least the old
+        // fee plus the feerate increase applied over the replacement's size. `Sub` gives us
+        // that increase directly from the two feerates; clamp it to zero since a `target_fee_rate`
+        // at or below `old_fee_rate` doesn't call for any extra relay-fee headroom.
+        let old_fee_rate = FeeRate::from_vb(old_fee, old_vsize);
+        let incremental_fee_rate = FeeRate::from_sat_per_vb(
+            (target_fee_rate - old_fee_rate).as_sat_per_vb().max(0.0),
+        );
+        let min_required_fee = old_fee + incremental_fee_rate.fee_vb(new_vsize);
+        let desired_fee = target_fee_rate.fee_vb(new_vsize).max(min_required_fee);
+        let extra_needed = desired_fee.saturating_sub(old_fee);
+
+        let change_headroom = match self.change_vout {
+            Some(vout) if self.allow_shrinking => self.original_tx.output[vout].value,
+            _ => 0,
+        };
+        let available = added_input_value + change_headroom;
+        if extra_needed > available {
+            return Err(Error::InsufficientFunds {
+                needed: extra_needed,
+                available,
+            });
+        }
+
+        let (new_fee, received_reduction) = if self.allow_shrinking {
+            let change_reduction = extra_needed.saturating_sub(added_input_value);
+            let change = &mut new_tx.output[self.change_vout.expect("checked by allow_shrinking")];
+            change.value -= change_reduction;
+            // Any `added_input_value` beyond what's needed to hit `desired_fee` is burned
+            // to fee rather than returned to change, so the real fee can exceed `desired_fee`.
+            (old_fee + added_input_value + change_reduction, change_reduction)
+        } else {
+            let fee_from_new_inputs = old_fee + added_input_value;
+            if fee_from_new_inputs < min_required_fee {
+                return Err(Error::FeeTooLow {
+                    required: min_required_fee,
+                });
+            }
+            (fee_from_new_inputs, 0)
+        };
+
+        let details = TransactionDetails {
+            transaction: Some(new_tx.clone()),
+            txid: new_tx.txid(),
+            // The change output is wallet-owned, so shrinking it lowers our received amount.
+            // Saturating: a caller can point `allow_shrinking` at any output index, including
+            // one whose value exceeds what this transaction is recorded as having received.
+            received: self.original_details.received.saturating_sub(received_reduction),
+            sent: self.original_details.sent + added_input_value,
+            fee: Some(new_fee),
+            vsize: Some(new_vsize),
+            confirmation_time: ConfirmationTime::Unconfirmed { last_seen: 0 },
+        };
+
+        Ok((new_tx, details))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec};
+    use core::str::FromStr;
+
+    use bitcoin::blockdata::script::Script;
+    use bitcoin::blockdata::transaction::{OutPoint, TxIn, TxOut};
+    use bitcoin::blockdata::witness::Witness;
+    use bitcoin::hash_types::Txid;
+
+    use crate::types::{KeychainKind, LocalUtxo, Utxo};
+
+    use super::*;
+
+    struct FixedFeeEstimator(FeeRate);
+
+    impl FeeEstimator for FixedFeeEstimator {
+        fn estimate(&self, _target_blocks: u16) -> Result<FeeRate, Error> {
+            Ok(self.0)
+        }
+    }
+
+    fn dummy_txid(byte: u8) -> Txid {
+        Txid::from_str(&format!("{:02x}", byte).repeat(32)).unwrap()
+    }
+
+    fn make_tx(change_value: u64, sequence: u32) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(dummy_txid(0xaa), 0),
+                script_sig: Script::new(),
+                sequence,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: change_value,
+                script_pubkey: Script::new(),
+            }],
+        }
+    }
+
+    fn make_details(tx: &Transaction, fee: u64, confirmation_time: ConfirmationTime) -> TransactionDetails {
+        TransactionDetails {
+            transaction: Some(tx.clone()),
+            txid: tx.txid(),
+            received: 1_000,
+            sent: 10_000,
+            fee: Some(fee),
+            vsize: Some(tx.weight().vbytes()),
+            confirmation_time,
+        }
+    }
+
+    /// Mirrors the production `min_required_fee`/`desired_fee` computation in `finish`, so
+    /// tests can assert against it without duplicating hand-derived arithmetic that could
+    /// drift from the implementation.
+    fn expected_desired_fee(old_fee: u64, vsize: usize, target_fee_rate: FeeRate) -> u64 {
+        let old_fee_rate = FeeRate::from_vb(old_fee, vsize);
+        let incremental_fee_rate =
+            FeeRate::from_sat_per_vb((target_fee_rate - old_fee_rate).as_sat_per_vb().max(0.0));
+        let min_required_fee = old_fee + incremental_fee_rate.fee_vb(vsize);
+        target_fee_rate.fee_vb(vsize).max(min_required_fee)
+    }
+
+    #[test]
+    fn test_rejects_irreplaceable_transaction() {
+        let tx = make_tx(100_000, 0xFFFF_FFFF);
+        let details = make_details(&tx, 1_000, ConfirmationTime::Unconfirmed { last_seen: 0 });
+        let estimator = FixedFeeEstimator(FeeRate::from_sat_per_vb(5.0));
+
+        let result = FeeBumpBuilder::new(&tx, &details)
+            .fee_rate(FeeRate::from_sat_per_vb(10.0))
+            .finish(&estimator);
+
+        assert!(matches!(result, Err(Error::IrreplaceableTransaction)));
+    }
+
+    #[test]
+    fn test_rejects_confirmed_transaction() {
+        let tx = make_tx(100_000, RBF_SEQUENCE_THRESHOLD - 1);
+        let details = make_details(
+            &tx,
+            1_000,
+            ConfirmationTime::Confirmed {
+                height: 100,
+                time: 0,
+            },
+        );
+        let estimator = FixedFeeEstimator(FeeRate::from_sat_per_vb(5.0));
+
+        let result = FeeBumpBuilder::new(&tx, &details)
+            .fee_rate(FeeRate::from_sat_per_vb(10.0))
+            .finish(&estimator);
+
+        assert!(matches!(result, Err(Error::TransactionConfirmed(txid)) if txid == tx.txid()));
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_vout() {
+        let tx = make_tx(100_000, RBF_SEQUENCE_THRESHOLD - 1);
+        let details = make_details(&tx, 1_000, ConfirmationTime::Unconfirmed { last_seen: 0 });
+        let estimator = FixedFeeEstimator(FeeRate::from_sat_per_vb(5.0));
+
+        let result = FeeBumpBuilder::new(&tx, &details)
+            .allow_shrinking(5)
+            .finish(&estimator);
+
+        assert!(matches!(result, Err(Error::InvalidVout { vout: 5 })));
+    }
+
+    #[test]
+    fn test_shrink_path_reports_actual_fee() {
+        let change_value = 100_000;
+        let old_fee = 1_000;
+        let tx = make_tx(change_value, RBF_SEQUENCE_THRESHOLD - 1);
+        let details = make_details(&tx, old_fee, ConfirmationTime::Unconfirmed { last_seen: 0 });
+        let target_fee_rate = FeeRate::from_sat_per_vb(5.0);
+        let estimator = FixedFeeEstimator(target_fee_rate);
+
+        // No extra inputs are added, so the replacement's vsize equals the original's.
+        let vsize = tx.weight().vbytes();
+        let expected_fee = expected_desired_fee(old_fee, vsize, target_fee_rate);
+        let expected_reduction = expected_fee - old_fee;
+
+        let (new_tx, new_details) = FeeBumpBuilder::new(&tx, &details)
+            .allow_shrinking(0)
+            .finish(&estimator)
+            .unwrap();
+
+        assert_eq!(new_details.fee, Some(expected_fee));
+        assert_eq!(new_tx.output[0].value, change_value - expected_reduction);
+        assert_eq!(new_details.received, details.received - expected_reduction);
+    }
+
+    #[test]
+    fn test_shrink_path_received_never_underflows() {
+        // `allow_shrinking` targets an output larger than the wallet's recorded `received`,
+        // which nothing here forbids (e.g. a non-wallet output). `received` must saturate
+        // at zero instead of wrapping/panicking.
+        let change_value = 100_000;
+        let old_fee = 1_000;
+        let tx = make_tx(change_value, RBF_SEQUENCE_THRESHOLD - 1);
+        let mut details = make_details(&tx, old_fee, ConfirmationTime::Unconfirmed { last_seen: 0 });
+        details.received = 1;
+        // High enough above the original's ~16.7 sat/vB that the change output must shrink.
+        let estimator = FixedFeeEstimator(FeeRate::from_sat_per_vb(100.0));
+
+        let (_new_tx, new_details) = FeeBumpBuilder::new(&tx, &details)
+            .allow_shrinking(0)
+            .finish(&estimator)
+            .unwrap();
+
+        assert_eq!(new_details.received, 0);
+    }
+
+    #[test]
+    fn test_add_inputs_path_burns_excess_to_fee() {
+        let old_fee = 1_000;
+        let tx = make_tx(100_000, RBF_SEQUENCE_THRESHOLD - 1);
+        let details = make_details(&tx, old_fee, ConfirmationTime::Unconfirmed { last_seen: 0 });
+        let estimator = FixedFeeEstimator(FeeRate::from_sat_per_vb(5.0));
+
+        let extra_utxo = WeightedUtxo {
+            satisfaction_weight: 108,
+            utxo: Utxo::Local(LocalUtxo {
+                outpoint: OutPoint::new(dummy_txid(0xbb), 0),
+                txout: TxOut {
+                    value: 50_000,
+                    script_pubkey: Script::new(),
+                },
+                keychain: KeychainKind::External,
+                is_spent: false,
+                derivation_index: 0,
+                confirmation_time: ConfirmationTime::Confirmed { height: 1, time: 0 },
+            }),
+        };
+
+        let (new_tx, new_details) = FeeBumpBuilder::new(&tx, &details)
+            .add_utxos(vec![extra_utxo])
+            .finish(&estimator)
+            .unwrap();
+
+        // All of the added input's value goes to fee: there's no change output to return it to.
+        assert_eq!(new_details.fee, Some(old_fee + 50_000));
+        assert_eq!(new_details.sent, details.sent + 50_000);
+        assert_eq!(
+            new_details.vsize,
+            Some(tx.weight().vbytes() + TXIN_BASE_VBYTES + 108usize.vbytes())
+        );
+        assert_eq!(new_tx.input.len(), 2);
+    }
+
+    #[test]
+    fn test_insufficient_funds_boundary() {
+        let old_fee = 1_000;
+        let tx = make_tx(100_000, RBF_SEQUENCE_THRESHOLD - 1);
+        let details = make_details(&tx, old_fee, ConfirmationTime::Unconfirmed { last_seen: 0 });
+        let target_fee_rate = FeeRate::from_sat_per_vb(1_000.0);
+        let estimator = FixedFeeEstimator(target_fee_rate);
+
+        let vsize = tx.weight().vbytes();
+        let expected_needed = expected_desired_fee(old_fee, vsize, target_fee_rate) - old_fee;
+
+        let result = FeeBumpBuilder::new(&tx, &details).finish(&estimator);
+
+        assert!(matches!(
+            result,
+            Err(Error::InsufficientFunds { needed, available })
+                if needed == expected_needed && available == 0
+        ));
+    }
+}
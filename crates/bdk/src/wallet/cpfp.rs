@@ -0,0 +1,288 @@
+// Bitcoin Dev Kit
+// Written in 2020 by Alekos Filini <alekos.filini@gmail.com>
+//
+// Copyright (c) 2020-2021 Bitcoin Dev Kit Developers
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Child-pays-for-parent package fee-bumping.
+//!
+//! Raises the *effective* feerate of a stuck parent transaction by spending one of its
+//! outputs in a child, modeled on the anchor-channel fee-bumping pattern: the child's fee
+//! is sized so that the parent and child together clear the target package feerate.
+
+use alloc::vec::Vec;
+
+use bitcoin::blockdata::script::Script;
+use bitcoin::blockdata::transaction::{OutPoint, Transaction, TxIn, TxOut};
+
+use crate::error::Error;
+use crate::types::{FeeRate, Vbytes, WeightedUtxo, TXIN_BASE_VBYTES};
+
+/// Fixed overhead, in vbytes, of the child transaction's version, locktime, input/output
+/// counts, and its single change output, excluding the spent inputs themselves.
+const CHILD_BASE_VSIZE: usize = 51;
+
+/// A source of UTXOs and signing capability the CPFP builder can draw on, independent of
+/// any particular wallet implementation.
+pub trait WalletSource {
+    /// List the wallet's confirmed, spendable UTXOs, available to add to the child.
+    fn list_confirmed_utxos(&self) -> Vec<WeightedUtxo>;
+
+    /// A fresh change `scriptPubkey` to receive the child's output.
+    fn get_change_script(&self) -> Script;
+
+    /// Sign every input of `tx` that the wallet can satisfy.
+    fn sign_tx(&self, tx: &mut Transaction) -> Result<(), Error>;
+}
+
+/// Build a child transaction spending output `vout` of `parent` so that the parent/child
+/// package reaches `target` feerate.
+///
+/// `satisfaction_weight` is the witness/`scriptSig` weight needed to spend `parent`'s output,
+/// sized the same way as [`WeightedUtxo::satisfaction_weight`] — the parent transaction alone
+/// doesn't carry this, since it depends on the spending key/script, not the output itself.
+/// Additional confirmed UTXOs are drawn from `source` if that output alone can't cover the
+/// required child fee.
+pub fn build_cpfp(
+    parent: &Transaction,
+    vout: usize,
+    satisfaction_weight: usize,
+    parent_vsize: usize,
+    parent_fee: u64,
+    target: FeeRate,
+    source: &impl WalletSource,
+) -> Result<Transaction, Error> {
+    if vout >= parent.output.len() {
+        return Err(Error::InvalidVout { vout });
+    }
+
+    let change_script = source.get_change_script();
+    let mut confirmed_utxos = source.list_confirmed_utxos();
+    // Ascending by value: `pop()` below then pulls the largest UTXO first, reaching the
+    // target with as few extra inputs as possible.
+    confirmed_utxos.sort_by(|a, b| a.utxo.txout().value.cmp(&b.utxo.txout().value));
+
+    let parent_outpoint = OutPoint::new(parent.txid(), vout as u32);
+    let mut selected = Vec::new();
+    let mut input_value = parent.output[vout].value;
+    // One input so far (the parent output being spent).
+    let mut num_inputs = 1usize;
+    let mut child_weight = satisfaction_weight;
+
+    loop {
+        // `child_weight.vbytes()` covers each input's witness/`scriptSig` contents; each
+        // input's non-witness base (outpoint, sequence, empty scriptSig length) is added
+        // separately since `TXIN_BASE_VBYTES` is already in vbytes, not weight units.
+        let child_vsize =
+            CHILD_BASE_VSIZE + num_inputs * TXIN_BASE_VBYTES + child_weight.vbytes();
+        let package_vsize = parent_vsize + child_vsize;
+        let required_package_fee = target.fee_vb(package_vsize);
+        let child_fee = required_package_fee.saturating_sub(parent_fee);
+
+        if input_value > child_fee {
+            const ENABLE_RBF_NO_LOCKTIME: u32 = 0xFFFF_FFFD;
+
+            let mut tx_in = alloc::vec![TxIn {
+                previous_output: parent_outpoint,
+                script_sig: Script::new(),
+                sequence: ENABLE_RBF_NO_LOCKTIME,
+                witness: bitcoin::blockdata::witness::Witness::new(),
+            }];
+            tx_in.extend(selected.iter().map(|utxo: &WeightedUtxo| TxIn {
+                previous_output: utxo.utxo.outpoint(),
+                script_sig: Script::new(),
+                sequence: ENABLE_RBF_NO_LOCKTIME,
+                witness: bitcoin::blockdata::witness::Witness::new(),
+            }));
+
+            let mut child = Transaction {
+                version: 2,
+                lock_time: 0,
+                input: tx_in,
+                output: alloc::vec![TxOut {
+                    value: input_value - child_fee,
+                    script_pubkey: change_script,
+                }],
+            };
+
+            source.sign_tx(&mut child)?;
+            return Ok(child);
+        }
+
+        match confirmed_utxos.pop() {
+            Some(utxo) => {
+                input_value += utxo.utxo.txout().value;
+                num_inputs += 1;
+                child_weight += utxo.satisfaction_weight;
+                selected.push(utxo);
+            }
+            None => {
+                return Err(Error::InsufficientFunds {
+                    needed: child_fee,
+                    available: input_value,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::str::FromStr;
+
+    use alloc::format;
+    use bitcoin::hash_types::Txid;
+
+    use crate::types::{KeychainKind, LocalUtxo, Utxo};
+
+    use super::*;
+
+    struct MockSource {
+        confirmed: Vec<WeightedUtxo>,
+    }
+
+    impl WalletSource for MockSource {
+        fn list_confirmed_utxos(&self) -> Vec<WeightedUtxo> {
+            self.confirmed.clone()
+        }
+
+        fn get_change_script(&self) -> Script {
+            Script::new()
+        }
+
+        fn sign_tx(&self, _tx: &mut Transaction) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    fn dummy_txid(byte: u8) -> Txid {
+        Txid::from_str(&format!("{:02x}", byte).repeat(32)).unwrap()
+    }
+
+    fn weighted_utxo(id: u8, value: u64, satisfaction_weight: usize) -> WeightedUtxo {
+        WeightedUtxo {
+            satisfaction_weight,
+            utxo: Utxo::Local(LocalUtxo {
+                outpoint: OutPoint::new(dummy_txid(id), 0),
+                txout: TxOut {
+                    value,
+                    script_pubkey: Script::new(),
+                },
+                keychain: KeychainKind::External,
+                is_spent: false,
+                derivation_index: 0,
+                confirmation_time: bdk_chain::ConfirmationTime::Confirmed { height: 1, time: 0 },
+            }),
+        }
+    }
+
+    /// A single-output parent transaction, for spending output 0 in `build_cpfp`.
+    fn parent_tx(value: u64) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: 0,
+            input: alloc::vec![TxIn {
+                previous_output: OutPoint::new(dummy_txid(0xaa), 0),
+                script_sig: Script::new(),
+                sequence: 0xFFFF_FFFF,
+                witness: bitcoin::blockdata::witness::Witness::new(),
+            }],
+            output: alloc::vec![TxOut {
+                value,
+                script_pubkey: Script::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_selects_largest_utxo_first() {
+        let parent = parent_tx(10);
+        let source = MockSource {
+            confirmed: alloc::vec![
+                weighted_utxo(0x01, 5, 4),
+                weighted_utxo(0x02, 100, 4),
+                weighted_utxo(0x03, 20, 4),
+            ],
+        };
+
+        let child = build_cpfp(
+            &parent,
+            0,
+            0,
+            100,
+            150,
+            FeeRate::from_sat_per_vb(1.0),
+            &source,
+        )
+        .unwrap();
+
+        // Only the single largest confirmed UTXO (value 100) is needed to reach the
+        // target, so the child should have the parent output plus exactly one extra input.
+        assert_eq!(child.input.len(), 2);
+        assert_eq!(child.input[0].previous_output, OutPoint::new(parent.txid(), 0));
+        assert_eq!(
+            child.input[1].previous_output,
+            OutPoint::new(dummy_txid(0x02), 0)
+        );
+        assert_eq!(child.output[0].value, 26);
+    }
+
+    #[test]
+    fn test_no_combination_reaches_target() {
+        let parent = parent_tx(10);
+        let source = MockSource {
+            confirmed: alloc::vec![weighted_utxo(0x01, 5, 4), weighted_utxo(0x02, 5, 4)],
+        };
+
+        let result = build_cpfp(
+            &parent,
+            0,
+            0,
+            100,
+            0,
+            FeeRate::from_sat_per_vb(1_000.0),
+            &source,
+        );
+
+        assert!(matches!(result, Err(Error::InsufficientFunds { .. })));
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_vout() {
+        let parent = parent_tx(100);
+        let source = MockSource {
+            confirmed: Vec::new(),
+        };
+
+        let result = build_cpfp(&parent, 1, 0, 100, 0, FeeRate::from_sat_per_vb(1.0), &source);
+
+        assert!(matches!(result, Err(Error::InvalidVout { vout: 1 })));
+    }
+
+    #[test]
+    fn test_input_value_equal_to_child_fee_is_not_accepted() {
+        // child_fee = parent_vsize + CHILD_BASE_VSIZE + TXIN_BASE_VBYTES - parent_fee
+        //           = 59 + 51 + 41 - 0 = 151, exactly matching the parent output's value.
+        // The strict `input_value > child_fee` check intentionally rejects this rather than
+        // emitting a zero-value change output.
+        let parent = parent_tx(151);
+        let source = MockSource {
+            confirmed: Vec::new(),
+        };
+
+        let result = build_cpfp(&parent, 0, 0, 59, 0, FeeRate::from_sat_per_vb(1.0), &source);
+
+        assert!(matches!(
+            result,
+            Err(Error::InsufficientFunds {
+                needed: 151,
+                available: 151
+            })
+        ));
+    }
+}